@@ -3,15 +3,24 @@
 //! `minigrep` is a simple CLI program that can search a file, line by
 //! line, and return only those lines which contain a given substring.
 
+mod regex;
+
 use std::env;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 
 pub struct Config {
     pub query: String,
-    pub filename: String,
+    pub filename: Vec<String>,
     pub case_sensitive: bool,
+    pub regex: bool,
+    pub recursive: bool,
+    pub line_numbers: bool,
+    pub count_only: bool,
+    pub invert: bool,
+    pub before: usize,
+    pub after: usize,
 }
 
 impl Config {
@@ -23,37 +32,178 @@ impl Config {
             None => return Err("Didn't get a query string"),
         };
 
-        let filename = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Didn't get a file name"),
-        };
+        let mut filename = Vec::new();
+        let mut recursive = false;
+        let mut line_numbers = false;
+        let mut count_only = false;
+        let mut invert = false;
+        let mut before = 0;
+        let mut after = 0;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-r" => recursive = true,
+                "-n" => line_numbers = true,
+                "-c" => count_only = true,
+                "-v" => invert = true,
+                "-B" => before = parse_context_arg(&mut args)?,
+                "-A" => after = parse_context_arg(&mut args)?,
+                "-C" => {
+                    let n = parse_context_arg(&mut args)?;
+                    before = n;
+                    after = n;
+                }
+                _ => filename.push(arg),
+            }
+        }
+
+        if filename.is_empty() {
+            return Err("Didn't get a file name");
+        }
 
         let case_sensitive = env::var("CASE_INSENSITIVE").is_err();
+        let regex = env::var("REGEX").is_ok();
 
-        Ok(Config { query, filename, case_sensitive })
+        Ok(Config {
+            query,
+            filename,
+            case_sensitive,
+            regex,
+            recursive,
+            line_numbers,
+            count_only,
+            invert,
+            before,
+            after,
+        })
     }
 }
 
-/// Reads content from file, then searches and prints results
+/// Parses the numeric argument that follows `-A`, `-B` or `-C`
+fn parse_context_arg(args: &mut env::Args) -> Result<usize, &'static str> {
+    args.next()
+        .ok_or("Expected a number of lines after -A, -B or -C")?
+        .parse()
+        .map_err(|_| "Expected a number of lines after -A, -B or -C")
+}
+
+/// Reads content from each configured file (walking directories when
+/// `recursive` is set), then searches and prints results. When more than
+/// one file is searched, or `recursive` is set, each printed line is
+/// prefixed with its filename.
 pub fn run(config: Config) -> Result<(), Box<Error>> {
-    let mut f = File::open(config.filename)?;
+    let mut files = Vec::new();
+    for path in &config.filename {
+        collect_files(path, config.recursive, &mut files)?;
+    }
+
+    let show_filename = files.len() > 1 || config.recursive;
+    let with_context = config.before > 0 || config.after > 0;
+
+    for filename in &files {
+        let mut f = File::open(filename)?;
+
+        let mut contents = String::new();
+        f.read_to_string(&mut contents)?;
+
+        if with_context {
+            if config.count_only {
+                let results = search_lines(&config, &contents);
+                let count = results.len().to_string();
+                println!("{}", format_line(filename, &count, show_filename, None));
+                continue;
+            }
+
+            let results = search_with_context(
+                &config.query,
+                &contents,
+                config.before,
+                config.after,
+                config.case_sensitive,
+                config.regex,
+                config.invert,
+            );
 
-    let mut contents = String::new();
-    f.read_to_string(&mut contents)?;
+            for (line_number, line) in results {
+                let line_number = if config.line_numbers { line_number } else { None };
+                println!("{}", format_line(filename, line, show_filename, line_number));
+            }
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
+            continue;
+        }
+
+        let results = search_lines(&config, &contents);
+
+        if config.count_only {
+            let count = results.len().to_string();
+            println!("{}", format_line(filename, &count, show_filename, None));
+            continue;
+        }
+
+        for (line_number, line) in results {
+            let line_number = if config.line_numbers { Some(line_number) } else { None };
+            println!("{}", format_line(filename, line, show_filename, line_number));
+        }
+    }
+
+    Ok(())
+}
+
+/// Dispatches to the regex, case-sensitive or case-insensitive search,
+/// as selected by `config`, returning each matching line (each
+/// non-matching line when `invert` is set) with its 1-based line number.
+fn search_lines<'a>(config: &Config, contents: &'a str) -> Vec<(usize, &'a str)> {
+    if config.regex {
+        search_regex_with_lines(&config.query, contents, config.invert)
+    } else if config.case_sensitive {
+        search_with_lines(&config.query, contents, config.invert)
     } else {
-        search_case_insensitive(&config.query, &contents)
-    };
+        search_case_insensitive_with_lines(&config.query, contents, config.invert)
+    }
+}
+
+/// Collects the regular files to search starting at `path`, descending
+/// into directories when `recursive` is set.
+fn collect_files(path: &str, recursive: bool, files: &mut Vec<String>) -> Result<(), Box<Error>> {
+    let metadata = fs::metadata(path)?;
+
+    if !metadata.is_dir() {
+        files.push(path.to_string());
+        return Ok(());
+    }
+
+    if !recursive {
+        return Err(format!("{}: is a directory (pass -r to search it recursively)", path).into());
+    }
 
-    for line in results {
-        println!("{}", line);
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        collect_files(&entry_path.to_string_lossy(), recursive, files)?;
     }
 
     Ok(())
 }
 
+/// Formats a result line, prefixing it with its filename (`path:line`,
+/// like grep) when `show_filename` is set, and with its 1-based line
+/// number when `line_number` is `Some`.
+fn format_line(filename: &str, line: &str, show_filename: bool, line_number: Option<usize>) -> String {
+    let mut out = String::new();
+
+    if show_filename {
+        out.push_str(filename);
+        out.push(':');
+    }
+
+    if let Some(n) = line_number {
+        out.push_str(&n.to_string());
+        out.push(':');
+    }
+
+    out.push_str(line);
+    out
+}
+
 /// Performs a case-sensitive line-by-line search of a string
 ///
 /// # Examples
@@ -103,6 +253,143 @@ pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a st
         .collect()
 }
 
+/// Performs a line-by-line search using `pattern` as a regular expression
+///
+/// Supports `.`, `*`/`+`/`?`, `[...]` character classes, `^`/`$` anchors
+/// and `|` alternation.
+///
+/// # Examples
+///
+/// ```
+/// let pattern = "l.ne";
+///
+/// let text = "\
+/// Example text with
+/// multiple lines
+/// and some matches
+/// to our query.";
+///
+/// assert_eq!(
+///     vec!["multiple lines"],
+///     minigrep::search_regex(pattern, text)
+///     );
+/// ```
+pub fn search_regex<'a>(pattern: &str, contents: &'a str) -> Vec<&'a str> {
+    let re = regex::Regex::new(pattern);
+    contents.lines().filter(|line| re.is_match(line)).collect()
+}
+
+/// Like [`search`], but returns each kept line together with its 1-based
+/// line number, and keeps non-matching lines instead when `invert` is set
+///
+/// [`search`]: fn.search.html
+pub fn search_with_lines<'a>(query: &str, contents: &'a str, invert: bool) -> Vec<(usize, &'a str)> {
+    contents.lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains(query) != invert)
+        .map(|(i, line)| (i + 1, line))
+        .collect()
+}
+
+/// Like [`search_case_insensitive`], but returns each kept line together
+/// with its 1-based line number, and keeps non-matching lines instead
+/// when `invert` is set
+///
+/// [`search_case_insensitive`]: fn.search_case_insensitive.html
+pub fn search_case_insensitive_with_lines<'a>(
+    query: &str,
+    contents: &'a str,
+    invert: bool,
+) -> Vec<(usize, &'a str)> {
+    let query = query.to_lowercase();
+    contents.lines()
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&query) != invert)
+        .map(|(i, line)| (i + 1, line))
+        .collect()
+}
+
+/// Like [`search_regex`], but returns each kept line together with its
+/// 1-based line number, and keeps non-matching lines instead when
+/// `invert` is set
+///
+/// [`search_regex`]: fn.search_regex.html
+pub fn search_regex_with_lines<'a>(
+    pattern: &str,
+    contents: &'a str,
+    invert: bool,
+) -> Vec<(usize, &'a str)> {
+    let re = regex::Regex::new(pattern);
+    contents.lines()
+        .enumerate()
+        .filter(|(_, line)| re.is_match(line) != invert)
+        .map(|(i, line)| (i + 1, line))
+        .collect()
+}
+
+/// Returns the matching lines together with `before` lines of context
+/// before each match and `after` lines of context after it, like
+/// `grep -A`/`-B`/`-C`, each paired with its 1-based line number.
+/// Overlapping or adjacent context windows are merged; non-adjacent
+/// blocks are separated by a `(None, "--")` entry.
+///
+/// `query` is matched as a regular expression when `use_regex` is set
+/// (mirroring [`search_regex`]), otherwise as a literal substring
+/// honoring `case_sensitive`. `invert` keeps non-matching lines as the
+/// match set instead, the same as [`search_with_lines`].
+///
+/// [`search_regex`]: fn.search_regex.html
+/// [`search_with_lines`]: fn.search_with_lines.html
+pub fn search_with_context<'a>(
+    query: &str,
+    contents: &'a str,
+    before: usize,
+    after: usize,
+    case_sensitive: bool,
+    use_regex: bool,
+    invert: bool,
+) -> Vec<(Option<usize>, &'a str)> {
+    let lines: Vec<&'a str> = contents.lines().collect();
+    let query_lower = query.to_lowercase();
+    let re = if use_regex { Some(regex::Regex::new(query)) } else { None };
+
+    let ranges = lines.iter()
+        .enumerate()
+        .filter(|(_, line)| {
+            let is_match = if let Some(re) = &re {
+                re.is_match(line)
+            } else if case_sensitive {
+                line.contains(query)
+            } else {
+                line.to_lowercase().contains(&query_lower)
+            };
+            is_match != invert
+        })
+        .map(|(i, _)| {
+            let start = i.saturating_sub(before);
+            let end = (i + after).min(lines.len() - 1);
+            (start, end)
+        });
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (i, &(start, end)) in merged.iter().enumerate() {
+        if i > 0 {
+            out.push((None, "--"));
+        }
+        out.extend((start..=end).map(|i| (Some(i + 1), lines[i])));
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -135,4 +422,245 @@ Trust me.";
             search_case_insensitive(query, contents)
             );
     }
+
+    #[test]
+    fn regex_search() {
+        let pattern = "pro.*ive";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec!["safe, fast, productive."],
+            search_regex(pattern, contents)
+            );
+    }
+
+    #[test]
+    fn collect_files_recursive_walks_subdirectories() {
+        let dir = env::temp_dir().join("minigrep_test_collect_recursive");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+
+        let mut files = Vec::new();
+        collect_files(&dir.to_string_lossy(), true, &mut files).unwrap();
+        files.sort();
+
+        let mut expected = vec![
+            dir.join("a.txt").to_string_lossy().into_owned(),
+            dir.join("sub").join("b.txt").to_string_lossy().into_owned(),
+        ];
+        expected.sort();
+
+        assert_eq!(expected, files);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn collect_files_non_recursive_rejects_directories() {
+        let dir = env::temp_dir().join("minigrep_test_collect_non_recursive");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+
+        let mut files = Vec::new();
+        collect_files(&dir.join("a.txt").to_string_lossy(), false, &mut files).unwrap();
+        assert_eq!(vec![dir.join("a.txt").to_string_lossy().into_owned()], files);
+
+        let mut files = Vec::new();
+        assert!(collect_files(&dir.to_string_lossy(), false, &mut files).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn format_line_with_filename() {
+        assert_eq!(
+            "foo.txt:safe, fast, productive.",
+            format_line("foo.txt", "safe, fast, productive.", true, None)
+            );
+    }
+
+    #[test]
+    fn format_line_without_filename() {
+        assert_eq!(
+            "safe, fast, productive.",
+            format_line("foo.txt", "safe, fast, productive.", false, None)
+            );
+    }
+
+    #[test]
+    fn format_line_with_line_number() {
+        assert_eq!(
+            "2:safe, fast, productive.",
+            format_line("foo.txt", "safe, fast, productive.", false, Some(2))
+            );
+    }
+
+    #[test]
+    fn format_line_with_filename_and_line_number() {
+        assert_eq!(
+            "foo.txt:2:safe, fast, productive.",
+            format_line("foo.txt", "safe, fast, productive.", true, Some(2))
+            );
+    }
+
+    #[test]
+    fn search_with_lines_reports_line_numbers() {
+        let query = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(2, "safe, fast, productive.")],
+            search_with_lines(query, contents, false)
+            );
+    }
+
+    #[test]
+    fn search_with_lines_inverted() {
+        let query = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(1, "Rust:"), (3, "Pick three.")],
+            search_with_lines(query, contents, true)
+            );
+    }
+
+    #[test]
+    fn search_case_insensitive_with_lines_inverted() {
+        let query = "rUsT";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+
+        assert_eq!(
+            vec![(2, "safe, fast, productive."), (3, "Pick three.")],
+            search_case_insensitive_with_lines(query, contents, true)
+            );
+    }
+
+    #[test]
+    fn search_regex_with_lines_inverted() {
+        let pattern = "pro.*ive";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(1, "Rust:"), (3, "Pick three.")],
+            search_regex_with_lines(pattern, contents, true)
+            );
+    }
+
+    #[test]
+    fn count_only_counts_matching_lines() {
+        let query = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+This is also productive.";
+
+        assert_eq!(2, search_with_lines(query, contents, false).len());
+    }
+
+    #[test]
+    fn context_around_single_match() {
+        let contents = "\
+one
+two
+three
+four
+five";
+
+        assert_eq!(
+            vec![(Some(1), "one"), (Some(2), "two"), (Some(3), "three"), (Some(4), "four")],
+            search_with_context("three", contents, 2, 1, true, false, false)
+            );
+    }
+
+    #[test]
+    fn context_merges_overlapping_windows() {
+        let contents = "\
+one
+two
+three
+four
+five";
+
+        assert_eq!(
+            vec![(Some(1), "one"), (Some(2), "two"), (Some(3), "three"), (Some(4), "four")],
+            search_with_context("t", contents, 1, 1, true, false, false)
+            );
+    }
+
+    #[test]
+    fn context_separates_distant_blocks() {
+        let contents = "\
+match one
+gap
+gap
+gap
+gap
+match two";
+
+        assert_eq!(
+            vec![(Some(1), "match one"), (None, "--"), (Some(6), "match two")],
+            search_with_context("match", contents, 0, 0, true, false, false)
+            );
+    }
+
+    #[test]
+    fn context_dispatches_through_the_regex_engine() {
+        let contents = "\
+foo123
+bar
+baz456";
+
+        assert_eq!(
+            vec![(Some(1), "foo123"), (Some(2), "bar"), (Some(3), "baz456")],
+            search_with_context("[0-9]+", contents, 1, 0, true, true, false)
+            );
+    }
+
+    #[test]
+    fn context_honors_invert() {
+        let contents = "\
+one
+two
+three";
+
+        assert_eq!(
+            vec![(Some(1), "one"), (None, "--"), (Some(3), "three")],
+            search_with_context("two", contents, 0, 0, true, false, true)
+            );
+    }
+
+    #[test]
+    fn regex_search_alternation() {
+        let pattern = "safe|three";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec!["safe, fast, productive.", "Pick three."],
+            search_regex(pattern, contents)
+            );
+    }
 }
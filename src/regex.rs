@@ -0,0 +1,370 @@
+//! A small dependency-free regular expression engine.
+//!
+//! Patterns are compiled into a Thompson NFA (one instruction per
+//! primitive) and matched with the classic "simulate all active states at
+//! once" algorithm: at every input character we hold a set of currently
+//! live program counters, take the epsilon-closure of that set, and step
+//! each one forward. A match is found as soon as a `Match` instruction is
+//! live.
+//!
+//! Supported syntax: literal characters, `.` (any character), `*` `+` `?`
+//! (quantifiers), `[...]` / `[^...]` character classes (with `a-z` style
+//! ranges), `^` / `$` anchors, and top-level `|` alternation.
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+/// A compiled pattern, ready to be matched against many lines.
+pub(crate) struct Regex {
+    prog: Vec<Inst>,
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str) -> Regex {
+        Regex { prog: compile(pattern) }
+    }
+
+    /// Returns true if the pattern matches anywhere in `line`.
+    pub(crate) fn is_match(&self, line: &str) -> bool {
+        let chars: Vec<char> = line.chars().collect();
+
+        let mut current = Vec::new();
+        let mut visited = vec![false; self.prog.len()];
+        add_thread(&self.prog, &mut current, &mut visited, 0, 0, chars.len());
+
+        if current.iter().any(|&pc| matches!(self.prog[pc], Inst::Match)) {
+            return true;
+        }
+
+        for (pos, &c) in chars.iter().enumerate() {
+            let mut next = Vec::new();
+            let mut next_visited = vec![false; self.prog.len()];
+
+            for &pc in &current {
+                let advance = match &self.prog[pc] {
+                    Inst::Char(want) => *want == c,
+                    Inst::Any => true,
+                    Inst::Class(ranges, negate) => {
+                        let in_class = ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+                        in_class != *negate
+                    }
+                    _ => false,
+                };
+
+                if advance {
+                    add_thread(
+                        &self.prog,
+                        &mut next,
+                        &mut next_visited,
+                        pc + 1,
+                        pos + 1,
+                        chars.len(),
+                    );
+                }
+            }
+
+            if next.iter().any(|&pc| matches!(self.prog[pc], Inst::Match)) {
+                return true;
+            }
+
+            current = next;
+        }
+
+        false
+    }
+}
+
+/// Follows epsilon transitions (`Split`, `Jmp`, `Start`, `End`) from `pc`,
+/// adding every state that can consume a character (or accept) to `list`.
+fn add_thread(
+    prog: &[Inst],
+    list: &mut Vec<usize>,
+    visited: &mut Vec<bool>,
+    pc: usize,
+    pos: usize,
+    len: usize,
+) {
+    if visited[pc] {
+        return;
+    }
+    visited[pc] = true;
+
+    match &prog[pc] {
+        Inst::Jmp(target) => add_thread(prog, list, visited, *target, pos, len),
+        Inst::Split(a, b) => {
+            add_thread(prog, list, visited, *a, pos, len);
+            add_thread(prog, list, visited, *b, pos, len);
+        }
+        Inst::Start => {
+            if pos == 0 {
+                add_thread(prog, list, visited, pc + 1, pos, len);
+            }
+        }
+        Inst::End => {
+            if pos == len {
+                add_thread(prog, list, visited, pc + 1, pos, len);
+            }
+        }
+        Inst::Char(_) | Inst::Any | Inst::Class(..) | Inst::Match => list.push(pc),
+    }
+}
+
+fn compile(pattern: &str) -> Vec<Inst> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let branches = split_branches(&chars);
+
+    let mut prog = Vec::new();
+    let mut end_jmps = Vec::new();
+
+    for (i, branch) in branches.iter().enumerate() {
+        let is_last = i == branches.len() - 1;
+
+        let split_idx = if is_last {
+            None
+        } else {
+            let idx = prog.len();
+            prog.push(Inst::Split(0, 0)); // patched below
+            Some(idx)
+        };
+
+        if let Some(idx) = split_idx {
+            let branch_start = prog.len();
+            if let Inst::Split(a, _) = &mut prog[idx] {
+                *a = branch_start;
+            }
+        }
+
+        compile_branch(branch, &mut prog);
+
+        if !is_last {
+            let jmp_idx = prog.len();
+            prog.push(Inst::Jmp(0)); // patched once the end is known
+            end_jmps.push(jmp_idx);
+
+            let next_branch = prog.len();
+            if let Some(idx) = split_idx {
+                if let Inst::Split(_, b) = &mut prog[idx] {
+                    *b = next_branch;
+                }
+            }
+        }
+    }
+
+    prog.push(Inst::Match);
+    let end = prog.len() - 1;
+    for idx in end_jmps {
+        prog[idx] = Inst::Jmp(end);
+    }
+
+    prog
+}
+
+/// Compiles a single alternation branch, prefixing it with an
+/// unanchored ".*?" search (racing it alongside the real program) unless
+/// the branch itself starts with `^`. Anchoring is a per-branch concern:
+/// `^abc|def` must still let `def` match anywhere in the line.
+fn compile_branch(chars: &[char], prog: &mut Vec<Inst>) {
+    let anchored = chars.first() == Some(&'^');
+
+    if !anchored {
+        let base = prog.len();
+        prog.push(Inst::Split(base + 1, base + 3));
+        prog.push(Inst::Any);
+        prog.push(Inst::Jmp(base));
+    }
+
+    compile_concat(chars, prog);
+}
+
+/// Splits a pattern into its top-level `|` branches, ignoring `|` inside
+/// `[...]` character classes.
+fn split_branches(chars: &[char]) -> Vec<Vec<char>> {
+    let mut branches = Vec::new();
+    let mut current = Vec::new();
+    let mut in_class = false;
+
+    for &c in chars {
+        match c {
+            '[' if !in_class => {
+                in_class = true;
+                current.push(c);
+            }
+            ']' if in_class => {
+                in_class = false;
+                current.push(c);
+            }
+            '|' if !in_class => {
+                branches.push(std::mem::take(&mut current));
+            }
+            _ => current.push(c),
+        }
+    }
+    branches.push(current);
+
+    branches
+}
+
+fn compile_concat(chars: &[char], prog: &mut Vec<Inst>) {
+    let mut pos = 0;
+    while pos < chars.len() {
+        let atom_start = prog.len();
+        pos += compile_atom(chars, pos, prog);
+
+        if pos < chars.len() {
+            match chars[pos] {
+                '*' => {
+                    wrap_star(prog, atom_start);
+                    pos += 1;
+                }
+                '+' => {
+                    wrap_plus(prog, atom_start);
+                    pos += 1;
+                }
+                '?' => {
+                    wrap_question(prog, atom_start);
+                    pos += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Compiles a single atom (literal, `.`, class, or anchor) at `pos`,
+/// pushing exactly one instruction, and returns how many pattern
+/// characters it consumed.
+fn compile_atom(chars: &[char], pos: usize, prog: &mut Vec<Inst>) -> usize {
+    match chars[pos] {
+        '^' => {
+            prog.push(Inst::Start);
+            1
+        }
+        '$' => {
+            prog.push(Inst::End);
+            1
+        }
+        '.' => {
+            prog.push(Inst::Any);
+            1
+        }
+        '[' => compile_class(chars, pos, prog),
+        '\\' if pos + 1 < chars.len() => {
+            prog.push(Inst::Char(chars[pos + 1]));
+            2
+        }
+        c => {
+            prog.push(Inst::Char(c));
+            1
+        }
+    }
+}
+
+fn compile_class(chars: &[char], pos: usize, prog: &mut Vec<Inst>) -> usize {
+    let mut i = pos + 1;
+    let negate = i < chars.len() && chars[i] == '^';
+    if negate {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    while i < chars.len() && chars[i] != ']' {
+        if i + 2 < chars.len() && chars[i + 1] == '-' && chars[i + 2] != ']' {
+            ranges.push((chars[i], chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((chars[i], chars[i]));
+            i += 1;
+        }
+    }
+    // Skip the closing ']', if present.
+    if i < chars.len() {
+        i += 1;
+    }
+
+    prog.push(Inst::Class(ranges, negate));
+    i - pos
+}
+
+/// Rewrites the single-instruction atom at `atom_start` (the top of
+/// `prog`) into `(Split atom, out) atom (Jmp split)`.
+fn wrap_star(prog: &mut Vec<Inst>, atom_start: usize) {
+    let atom = prog.remove(atom_start);
+    prog.insert(atom_start, Inst::Split(atom_start + 1, atom_start + 3));
+    prog.insert(atom_start + 1, atom);
+    prog.push(Inst::Jmp(atom_start));
+}
+
+/// `atom (Split atom, out)`.
+fn wrap_plus(prog: &mut Vec<Inst>, atom_start: usize) {
+    prog.push(Inst::Split(atom_start, prog.len() + 1));
+}
+
+/// `(Split atom, out) atom`.
+fn wrap_question(prog: &mut Vec<Inst>, atom_start: usize) {
+    let atom = prog.remove(atom_start);
+    prog.insert(atom_start, Inst::Split(atom_start + 1, atom_start + 2));
+    prog.insert(atom_start + 1, atom);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn literal() {
+        assert!(Regex::new("duct").is_match("productive"));
+        assert!(!Regex::new("duct").is_match("nope"));
+    }
+
+    #[test]
+    fn dot_and_star() {
+        assert!(Regex::new("a.c").is_match("xabcx"));
+        assert!(Regex::new("ab*c").is_match("ac"));
+        assert!(Regex::new("ab*c").is_match("abbbc"));
+        assert!(!Regex::new("ab+c").is_match("ac"));
+    }
+
+    #[test]
+    fn question_mark() {
+        assert!(Regex::new("colou?r").is_match("color"));
+        assert!(Regex::new("colou?r").is_match("colour"));
+    }
+
+    #[test]
+    fn char_class() {
+        assert!(Regex::new("[a-c]at").is_match("bat"));
+        assert!(!Regex::new("[a-c]at").is_match("dat"));
+        assert!(Regex::new("[^a-c]at").is_match("dat"));
+    }
+
+    #[test]
+    fn anchors() {
+        assert!(Regex::new("^Rust").is_match("Rust: safe"));
+        assert!(!Regex::new("^Rust").is_match("about Rust"));
+        assert!(Regex::new("three$").is_match("Pick three"));
+        assert!(!Regex::new("three$").is_match("three things"));
+    }
+
+    #[test]
+    fn alternation() {
+        assert!(Regex::new("cat|dog").is_match("my dog"));
+        assert!(Regex::new("cat|dog").is_match("my cat"));
+        assert!(!Regex::new("cat|dog").is_match("my fish"));
+    }
+
+    #[test]
+    fn alternation_anchors_only_the_branch_they_appear_in() {
+        assert!(Regex::new("^abc|def").is_match("xxxdef"));
+        assert!(Regex::new("^abc").is_match("abcdef"));
+        assert!(!Regex::new("^abc|def").is_match("xxxabc"));
+    }
+}